@@ -3,118 +3,165 @@
     windows_subsystem = "windows"
 )]
 
+mod migrations;
+mod sync;
+mod tournaments;
+
+use r2d2::{CustomizeConnection, Pool};
+use r2d2_sqlite::SqliteConnectionManager;
 use rusqlite::{Connection, Result as SqlResult};
 use std::fs;
 use std::path::PathBuf;
-use std::sync::Mutex;
 
-/// figure out where to place the sqlite db file
-/// we'll create `<project-root>/sqlite/esports_ranker.db`
-/// and make sure the `sqlite/` directory exists.
-fn resolve_db_path() -> SqlResult<PathBuf> {
-    // current_dir() when running `pnpm tauri dev` is usually `desktop/`
-    // but when the compiled binary runs, it can sometimes be `desktop/src-tauri/target/...`
-    // so we'll walk up until we find the project root that has a `sqlite` folder or can create it.
-
-    // start from the current working directory
-    let mut dir = std::env::current_dir()
-        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
-
-    // We'll walk up at most 3 levels to be safe: current, parent, grandparent
-    // and stop when we can create/use `<that>/sqlite`
-    for _ in 0..3 {
-        let candidate_sqlite_dir = dir.join("sqlite");
-
-        // try to create the sqlite dir if it doesn't exist
-        if !candidate_sqlite_dir.exists() {
-            if let Err(_) = fs::create_dir_all(&candidate_sqlite_dir) {
-                // couldn't create here, so try going up a level
-                dir = match dir.parent() {
-                    Some(parent) => parent.to_path_buf(),
-                    None => break,
-                };
-                continue;
-            }
+/// Pooled SQLite connections shared across Tauri commands.
+type DbPool = Pool<SqliteConnectionManager>;
+
+/// Wrap a failure to resolve the database path as a `CannotOpen` error,
+/// carrying `context` plus the underlying cause so the log says *why*.
+fn cannot_open(context: &str, cause: impl std::fmt::Display) -> rusqlite::Error {
+    rusqlite::Error::SqliteFailure(
+        rusqlite::ffi::Error {
+            code: rusqlite::ErrorCode::CannotOpen,
+            extended_code: 14,
+        },
+        Some(format!("{context}: {cause}")),
+    )
+}
+
+/// Create `path`'s parent directory (if it has one) and return `path` as
+/// the resolved db location.
+fn use_override_path(path: PathBuf) -> SqlResult<PathBuf> {
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)
+                .map_err(|e| cannot_open("could not create database directory", e))?;
         }
+    }
+    Ok(path)
+}
 
-        // We were able to ensure this sqlite/ dir exists here.
-        let db_path = candidate_sqlite_dir.join("esports_ranker.db");
-        return Ok(db_path);
+/// Figure out where to place the sqlite db file.
+///
+/// The path is deterministic rather than relative to the (unpredictable)
+/// working directory of an installed binary:
+/// - If `ESPORTS_RANKER_DB` is set, it's taken as a full path to the db
+///   file, verbatim (handy for dev and tests).
+/// - `DATABASE_URL` is also honored for the common `sqlite:` URL
+///   convention (e.g. `sqlite:///abs/path/to.db` or `sqlite://relative.db`);
+///   the scheme is stripped before the rest is used as a path.
+/// - Otherwise the file lives under the platform app-data directory,
+///   e.g. `%APPDATA%\esports-ranker`, `~/Library/Application Support/esports-ranker`,
+///   or `~/.local/share/esports-ranker`.
+fn resolve_db_path() -> SqlResult<PathBuf> {
+    // An explicit override wins and is taken as a full path to the db file.
+    if let Some(path) = std::env::var_os("ESPORTS_RANKER_DB") {
+        return use_override_path(PathBuf::from(path));
     }
 
-    // if we exit the loop without returning, give up with a meaningful error
-    Err(rusqlite::Error::SqliteFailure(
+    if let Some(url) = std::env::var_os("DATABASE_URL").and_then(|v| v.into_string().ok()) {
+        // `DATABASE_URL` follows the `sqlite:` URL convention, unlike
+        // `ESPORTS_RANKER_DB` which is a raw path; strip the scheme (and
+        // the `//` of an authority-style URL, if present) before using it.
+        let path = url
+            .strip_prefix("sqlite:")
+            .map(|rest| rest.trim_start_matches("//"))
+            .unwrap_or(&url);
+        return use_override_path(PathBuf::from(path));
+    }
+
+    let data_dir = dirs::data_dir()
+        .ok_or_else(|| cannot_open("no app-data directory", "platform data directory is unknown"))?
+        .join("esports-ranker");
+
+    fs::create_dir_all(&data_dir)
+        .map_err(|e| cannot_open("could not create app-data directory", e))?;
+
+    Ok(data_dir.join("esports_ranker.db"))
+}
+
+/// Map a [`migrations::MigrationError`] into the `rusqlite::Error` that
+/// [`init_database`] returns, so callers keep a single error type.
+fn migration_error(e: migrations::MigrationError) -> rusqlite::Error {
+    rusqlite::Error::SqliteFailure(
+        rusqlite::ffi::Error {
+            code: rusqlite::ErrorCode::SchemaChanged,
+            extended_code: 1,
+        },
+        Some(e.to_string()),
+    )
+}
+
+/// Build a `CannotOpen` error out of a pool-level failure.
+fn pool_error(cause: impl std::fmt::Display) -> rusqlite::Error {
+    rusqlite::Error::SqliteFailure(
         rusqlite::ffi::Error {
             code: rusqlite::ErrorCode::CannotOpen,
-            extended_code: 14,
+            extended_code: 1,
         },
-        Some("could not resolve sqlite directory".into()),
-    ))
+        Some(cause.to_string()),
+    )
 }
 
-/// Initialize (or open) the local SQLite database:
-/// - Enable WAL mode
-/// - Create the `tournaments` table
-fn init_database() -> SqlResult<Connection> {
-    let db_path = resolve_db_path()?;
-    let conn = Connection::open(&db_path)?;
-
-    // WAL mode for durability and fewer write locks
-    conn.pragma_update(None, "journal_mode", &"WAL")?;
-    conn.pragma_update(None, "synchronous", &"NORMAL")?;
-
-    // basic schema; we'll extend this later (stages, series, outbox, etc.)
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS tournaments (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            name TEXT NOT NULL,
-            region TEXT,
-            tier TEXT,
-            start_date TEXT,
-            end_date TEXT,
-            status TEXT DEFAULT 'Draft'
-        );",
-        [],
-    )?;
-
-    Ok(conn)
+/// Pool customizer that finalizes crsqlite before a connection is dropped,
+/// honoring the extension's requirement to `crsql_finalize()` on close.
+#[derive(Debug)]
+struct CrsqliteCustomizer;
+
+impl CustomizeConnection<Connection, rusqlite::Error> for CrsqliteCustomizer {
+    fn on_release(&self, conn: Connection) {
+        // Best-effort: a connection being torn down can't report errors.
+        let _ = sync::finalize(&conn);
+    }
 }
 
-/// Return a list of tournament names from the local DB.
-/// This is called from React via invoke("get_tournaments")
-#[tauri::command]
-fn get_tournaments(state: tauri::State<'_, Mutex<Connection>>) -> Result<Vec<String>, String> {
-    let conn = state
-        .lock()
-        .map_err(|_| "Failed to lock DB connection".to_string())?;
-
-    let mut stmt = conn
-        .prepare("SELECT name FROM tournaments ORDER BY id DESC")
-        .map_err(|e| e.to_string())?;
-
-    let rows = stmt
-        .query_map([], |row| row.get::<_, String>(0))
-        .map_err(|e| e.to_string())?;
-
-    let mut names = Vec::new();
-    for row_result in rows {
-        match row_result {
-            Ok(name) => names.push(name),
-            Err(e) => return Err(e.to_string()),
-        }
+/// Build the connection pool and bring the schema up to date:
+/// - Every pooled connection gets WAL + `synchronous=NORMAL` and loads
+///   crsqlite when the extension can be found
+/// - Schema migrations run once, then `tournaments` is upgraded to a CRR
+///   if crsqlite is available
+fn init_database() -> SqlResult<DbPool> {
+    let db_path = resolve_db_path()?;
+
+    // Apply the shared pragmas and load the CRDT extension on every
+    // connection the pool hands out.
+    let manager = SqliteConnectionManager::file(&db_path).with_init(|conn| {
+        conn.pragma_update(None, "journal_mode", &"WAL")?;
+        conn.pragma_update(None, "synchronous", &"NORMAL")?;
+        sync::load_crsqlite(conn)?;
+        Ok(())
+    });
+
+    let pool = Pool::builder()
+        .connection_customizer(Box::new(CrsqliteCustomizer))
+        .build(manager)
+        .map_err(pool_error)?;
+
+    // Run migrations and, when the CRDT extension is present, set up
+    // replication once on startup. A missing extension is non-fatal: the
+    // app falls back to a plain local database without sync.
+    let mut conn = pool.get().map_err(pool_error)?;
+    migrations::run(&mut conn).map_err(migration_error)?;
+    if sync::crsqlite_available() {
+        sync::init_crr(&conn)?;
     }
+    drop(conn);
 
-    Ok(names)
+    Ok(pool)
 }
 
 fn main() {
     tauri::Builder::default()
-        // DB connection is created once and shared via Tauri state
-        .manage(Mutex::new(
-            init_database().expect("failed to initialize local SQLite database"),
-        ))
+        // DB connection pool is created once and shared via Tauri state
+        .manage(init_database().expect("failed to initialize local SQLite database"))
         // expose commands to frontend
-        .invoke_handler(tauri::generate_handler![get_tournaments])
+        .invoke_handler(tauri::generate_handler![
+            tournaments::list_tournaments,
+            tournaments::create_tournament,
+            tournaments::update_tournament,
+            tournaments::delete_tournament,
+            sync::export_changes,
+            sync::apply_changes
+        ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
\ No newline at end of file
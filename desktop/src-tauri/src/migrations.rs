@@ -0,0 +1,165 @@
+//! Ordered, versioned schema migrations for the local SQLite database.
+//!
+//! The database tracks its own schema version in `PRAGMA user_version`.
+//! On open we apply every migration whose `version` is greater than the
+//! stored version, in order, inside a single transaction and bump
+//! `user_version` after each one. If anything fails the whole batch rolls
+//! back, so the on-disk schema never ends up half-migrated.
+
+use rusqlite::{Connection, Error as SqlError};
+
+/// A single forward schema migration.
+pub struct Migration {
+    /// The `user_version` the database is at *after* this migration runs.
+    pub version: i64,
+    /// SQL executed (as a script) to reach `version`.
+    pub sql: &'static str,
+}
+
+/// The full, ordered set of migrations. Append new entries here with the
+/// next version number; never edit or reorder the ones already shipped.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        // `id` is an app-generated UUID rather than an autoincrement rowid:
+        // cr-sqlite merges CRR rows by primary key, so two disconnected
+        // installs must never mint the same id for distinct tournaments.
+        sql: "CREATE TABLE IF NOT EXISTS tournaments (
+            id TEXT PRIMARY KEY NOT NULL,
+            -- cr-sqlite requires every CRR column to be nullable or carry a
+            -- default, so a partially-merged row is always insertable;
+            -- app-level `validate_name` is what actually keeps this non-blank.
+            name TEXT NOT NULL DEFAULT '',
+            region TEXT,
+            tier TEXT,
+            start_date TEXT,
+            end_date TEXT,
+            status TEXT DEFAULT 'Draft',
+            -- Random UUID ids carry no ordering, so keep an insertion
+            -- timestamp to drive a stable "newest first" list order.
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );",
+    },
+    Migration {
+        version: 2,
+        sql: "CREATE TABLE IF NOT EXISTS sync_peers (
+            site_id BLOB PRIMARY KEY,
+            last_seen_db_version INTEGER NOT NULL DEFAULT 0
+        );
+        CREATE TABLE IF NOT EXISTS site_config (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        );",
+    },
+];
+
+/// The newest schema version this build knows how to produce.
+pub const CURRENT_DB_VERSION: i64 = MIGRATIONS.len() as i64;
+
+/// Something went wrong while migrating the schema.
+#[derive(Debug)]
+pub enum MigrationError {
+    /// A SQL statement (or the surrounding transaction) failed.
+    Sqlite(SqlError),
+    /// The on-disk database is newer than this build understands, so we
+    /// refuse to touch it rather than corrupt a future schema.
+    UnsupportedVersion { found: i64, supported: i64 },
+}
+
+impl std::fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MigrationError::Sqlite(e) => write!(f, "{e}"),
+            MigrationError::UnsupportedVersion { found, supported } => write!(
+                f,
+                "database version {found} is newer than supported version {supported}; \
+                 please update the application"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MigrationError {}
+
+impl From<SqlError> for MigrationError {
+    fn from(e: SqlError) -> Self {
+        MigrationError::Sqlite(e)
+    }
+}
+
+/// Bring `conn` up to [`CURRENT_DB_VERSION`], applying every pending
+/// migration in a single transaction.
+pub fn run(conn: &mut Connection) -> Result<(), MigrationError> {
+    let current: i64 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+
+    if current > CURRENT_DB_VERSION {
+        return Err(MigrationError::UnsupportedVersion {
+            found: current,
+            supported: CURRENT_DB_VERSION,
+        });
+    }
+
+    if current == CURRENT_DB_VERSION {
+        return Ok(());
+    }
+
+    let tx = conn.transaction()?;
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        tx.execute_batch(migration.sql)?;
+        tx.pragma_update(None, "user_version", migration.version)?;
+    }
+    tx.commit()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrates_from_scratch() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        run(&mut conn).unwrap();
+
+        let version: i64 = conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, CURRENT_DB_VERSION);
+
+        let tables: Vec<String> = conn
+            .prepare("SELECT name FROM sqlite_master WHERE type = 'table' ORDER BY name")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert!(tables.contains(&"tournaments".to_string()));
+    }
+
+    #[test]
+    fn rerun_is_a_noop() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        run(&mut conn).unwrap();
+        // Running again against an already-current DB should not error.
+        run(&mut conn).unwrap();
+        let version: i64 = conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, CURRENT_DB_VERSION);
+    }
+
+    #[test]
+    fn rejects_future_versions() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "user_version", CURRENT_DB_VERSION + 1)
+            .unwrap();
+        match run(&mut conn) {
+            Err(MigrationError::UnsupportedVersion { found, supported }) => {
+                assert_eq!(found, CURRENT_DB_VERSION + 1);
+                assert_eq!(supported, CURRENT_DB_VERSION);
+            }
+            other => panic!("expected UnsupportedVersion, got {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,349 @@
+//! Offline-first multi-device sync built on the `cr-sqlite` CRDT extension.
+//!
+//! The `tournaments` table is upgraded to a conflict-free replicated
+//! relation (CRR) so two installs can edit while disconnected and converge
+//! deterministically (last-writer-wins per column). Changes move between
+//! peers as rows of the `crsql_changes` virtual table: [`export_changes`]
+//! reads everything newer than a peer's last-seen `db_version`, and
+//! [`apply_changes`] feeds foreign changes back in so the merge logic runs.
+//!
+//! The bundled extension is pinned to **cr-sqlite v0.16.3**, whose
+//! `crsql_changes` shape is the nine columns mirrored by [`Change`]
+//! (`table, pk, cid, val, col_version, db_version, site_id, cl, seq`).
+//! The `cl` (causal length) and `seq` columns carry delete/resurrect
+//! bookkeeping; dropping them from the round-trip corrupts the merge on
+//! the receiving side, so the SELECT and INSERT below must stay in lockstep
+//! with the pinned version.
+
+use rusqlite::types::{Value, ValueRef};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::DbPool;
+
+/// Filename of the bundled `cr-sqlite` loadable extension for this target.
+fn crsqlite_filename() -> &'static str {
+    #[cfg(target_os = "windows")]
+    {
+        "crsqlite.dll"
+    }
+    #[cfg(target_os = "macos")]
+    {
+        "crsqlite.dylib"
+    }
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+        "crsqlite.so"
+    }
+}
+
+/// Locate the bundled extension, returning `None` when it can't be found.
+///
+/// Search order, first hit wins:
+/// 1. the `ESPORTS_RANKER_CRSQLITE` env var (an explicit path, for dev/tests),
+/// 2. the directory of the running executable (the installed layout),
+/// 3. the current working directory (handy under `tauri dev`/`cargo test`).
+fn crsqlite_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("ESPORTS_RANKER_CRSQLITE") {
+        let path = PathBuf::from(path);
+        if path.exists() {
+            return Some(path);
+        }
+    }
+
+    let filename = crsqlite_filename();
+    let mut candidates = Vec::new();
+    if let Some(dir) = std::env::current_exe().ok().and_then(|p| p.parent().map(PathBuf::from)) {
+        candidates.push(dir.join(filename));
+    }
+    if let Ok(cwd) = std::env::current_dir() {
+        candidates.push(cwd.join(filename));
+    }
+    candidates.into_iter().find(|p| p.exists())
+}
+
+/// Whether the `cr-sqlite` extension is available on this install.
+///
+/// Sync is an opt-in capability: when the extension is missing the app
+/// still starts with a plain local database, so callers gate CRR setup on
+/// this rather than treating absence as fatal.
+pub fn crsqlite_available() -> bool {
+    crsqlite_path().is_some()
+}
+
+/// Load the `cr-sqlite` extension into a freshly opened connection, returning
+/// `Ok(false)` if no extension could be located.
+///
+/// Runs on *every* pooled connection — the CRR tables depend on the
+/// extension's virtual tables and triggers being available. A missing
+/// extension is deliberately non-fatal so the app keeps working without sync.
+pub fn load_crsqlite(conn: &Connection) -> rusqlite::Result<bool> {
+    let Some(path) = crsqlite_path() else {
+        return Ok(false);
+    };
+
+    // SAFETY: the extension path comes from the app bundle or a trusted env
+    // override, not user input, and we re-disable loading immediately after.
+    unsafe {
+        conn.load_extension_enable()?;
+        let result = conn.load_extension(path, Some("sqlite3_crsqlite_init"));
+        conn.load_extension_disable()?;
+        result?;
+    }
+    Ok(true)
+}
+
+/// Upgrade the `tournaments` table to a CRR and make sure this install has
+/// a persisted, stable `site_id`. Idempotent — safe to call on every start.
+pub fn init_crr(conn: &Connection) -> rusqlite::Result<()> {
+    conn.query_row("SELECT crsql_as_crr('tournaments')", [], |_| Ok(()))?;
+
+    // crsqlite maintains a stable per-database site id; persist its hex once
+    // so the rest of the app has a single source of truth.
+    conn.execute(
+        "INSERT OR IGNORE INTO site_config (key, value)
+         VALUES ('site_id', lower(hex(crsql_site_id())))",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Release crsqlite's internal resources before a connection is closed.
+///
+/// `crsql_finalize` must run before every connection drops, otherwise the
+/// extension leaks prepared statements and can leave the WAL wedged.
+pub fn finalize(conn: &Connection) -> rusqlite::Result<()> {
+    conn.query_row("SELECT crsql_finalize()", [], |_| Ok(()))?;
+    Ok(())
+}
+
+/// A single row of the `crsql_changes` virtual table, serialized for the wire.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Change {
+    pub table: String,
+    pub pk: Vec<u8>,
+    pub cid: String,
+    pub val: serde_json::Value,
+    pub col_version: i64,
+    pub db_version: i64,
+    pub site_id: Vec<u8>,
+    pub cl: i64,
+    pub seq: i64,
+}
+
+/// Payload returned from [`export_changes`]: the changes plus this site's id
+/// and the highest `db_version` it currently holds (for incremental resumes).
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChangeSet {
+    pub site_id: Vec<u8>,
+    pub max_db_version: i64,
+    pub changes: Vec<Change>,
+}
+
+fn value_ref_to_json(value: ValueRef<'_>) -> serde_json::Value {
+    match value {
+        ValueRef::Null => serde_json::Value::Null,
+        ValueRef::Integer(i) => serde_json::Value::from(i),
+        ValueRef::Real(f) => serde_json::Value::from(f),
+        ValueRef::Text(t) => serde_json::Value::from(String::from_utf8_lossy(t).into_owned()),
+        // Blobs round-trip as a byte array so arbitrary payloads survive JSON.
+        ValueRef::Blob(b) => serde_json::Value::from(b.to_vec()),
+    }
+}
+
+fn json_to_value(value: &serde_json::Value) -> Value {
+    match value {
+        serde_json::Value::Null => Value::Null,
+        serde_json::Value::Bool(b) => Value::Integer(*b as i64),
+        serde_json::Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                Value::Integer(i)
+            } else {
+                Value::Real(n.as_f64().unwrap_or(0.0))
+            }
+        }
+        serde_json::Value::String(s) => Value::Text(s.clone()),
+        // A JSON array is how we encoded a blob on the way out.
+        serde_json::Value::Array(items) => Value::Blob(
+            items
+                .iter()
+                .filter_map(|v| v.as_u64().map(|b| b as u8))
+                .collect(),
+        ),
+        serde_json::Value::Object(_) => Value::Null,
+    }
+}
+
+/// Read every change newer than `since_version` so a peer can catch up.
+pub fn export_changes_conn(conn: &Connection, since_version: i64) -> rusqlite::Result<ChangeSet> {
+    let site_id: Vec<u8> = conn.query_row("SELECT crsql_site_id()", [], |row| row.get(0))?;
+    let max_db_version: i64 = conn.query_row("SELECT crsql_db_version()", [], |row| row.get(0))?;
+
+    let mut stmt = conn.prepare(
+        "SELECT \"table\", pk, cid, val, col_version, db_version, site_id, cl, seq
+         FROM crsql_changes
+         WHERE db_version > ?1
+         ORDER BY db_version ASC",
+    )?;
+
+    let rows = stmt.query_map([since_version], |row| {
+        Ok(Change {
+            table: row.get(0)?,
+            pk: row.get(1)?,
+            cid: row.get(2)?,
+            val: value_ref_to_json(row.get_ref(3)?),
+            col_version: row.get(4)?,
+            db_version: row.get(5)?,
+            site_id: row.get(6)?,
+            cl: row.get(7)?,
+            seq: row.get(8)?,
+        })
+    })?;
+
+    let mut changes = Vec::new();
+    for row in rows {
+        changes.push(row?);
+    }
+
+    Ok(ChangeSet {
+        site_id,
+        max_db_version,
+        changes,
+    })
+}
+
+/// Apply a peer's [`ChangeSet`] inside a single transaction so crsqlite's
+/// column-version merge runs atomically, then remember how far that peer
+/// has been merged for the next incremental export.
+pub fn apply_changes_conn(conn: &mut Connection, changes: &ChangeSet) -> rusqlite::Result<()> {
+    let tx = conn.transaction()?;
+
+    {
+        let mut stmt = tx.prepare(
+            "INSERT INTO crsql_changes
+             (\"table\", pk, cid, val, col_version, db_version, site_id, cl, seq)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        )?;
+
+        for change in &changes.changes {
+            stmt.execute(rusqlite::params![
+                change.table,
+                change.pk,
+                change.cid,
+                json_to_value(&change.val),
+                change.col_version,
+                change.db_version,
+                change.site_id,
+                change.cl,
+                change.seq,
+            ])?;
+        }
+    }
+
+    // Track the peer's high-water mark so future exports stay incremental.
+    tx.execute(
+        "INSERT INTO sync_peers (site_id, last_seen_db_version)
+         VALUES (?1, ?2)
+         ON CONFLICT(site_id) DO UPDATE SET
+             last_seen_db_version = max(last_seen_db_version, excluded.last_seen_db_version)",
+        rusqlite::params![changes.site_id, changes.max_db_version],
+    )?;
+
+    tx.commit()?;
+    Ok(())
+}
+
+/// Read every change newer than `since_version` so a peer can catch up.
+#[tauri::command]
+pub fn export_changes(
+    state: tauri::State<'_, DbPool>,
+    since_version: i64,
+) -> Result<ChangeSet, String> {
+    let conn = state.get().map_err(|e| e.to_string())?;
+    export_changes_conn(&conn, since_version).map_err(|e| e.to_string())
+}
+
+/// Apply a peer's [`ChangeSet`], resolving conflicts via crsqlite's merge.
+#[tauri::command]
+pub fn apply_changes(state: tauri::State<'_, DbPool>, changes: ChangeSet) -> Result<(), String> {
+    let mut conn = state.get().map_err(|e| e.to_string())?;
+    apply_changes_conn(&mut conn, &changes).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Open an in-memory replica with crsqlite loaded, migrated, and the
+    /// `tournaments` table upgraded to a CRR.
+    fn open_replica() -> Connection {
+        let mut conn = Connection::open_in_memory().unwrap();
+        assert!(load_crsqlite(&conn).unwrap());
+        crate::migrations::run(&mut conn).unwrap();
+        init_crr(&conn).unwrap();
+        conn
+    }
+
+    #[test]
+    fn changes_round_trip_converges() {
+        // The extension is a per-target build artifact, not checked into
+        // source, so a plain `cargo test` on a fresh clone won't find it;
+        // skip rather than fail, mirroring `init_database`'s own fallback.
+        if !crsqlite_available() {
+            eprintln!("skipping changes_round_trip_converges: cr-sqlite extension not found");
+            return;
+        }
+        let a = open_replica();
+        let mut b = open_replica();
+
+        a.execute(
+            "INSERT INTO tournaments (id, name, region) VALUES ('t1', 'Worlds', 'Global')",
+            [],
+        )
+        .unwrap();
+
+        let set = export_changes_conn(&a, 0).unwrap();
+        assert!(!set.changes.is_empty());
+        assert!(set.max_db_version > 0);
+
+        apply_changes_conn(&mut b, &set).unwrap();
+
+        let (name, region): (String, String) = b
+            .query_row(
+                "SELECT name, region FROM tournaments WHERE id = 't1'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(name, "Worlds");
+        assert_eq!(region, "Global");
+
+        finalize(&a).unwrap();
+        finalize(&b).unwrap();
+    }
+
+    #[test]
+    fn export_is_incremental_by_db_version() {
+        if !crsqlite_available() {
+            eprintln!("skipping export_is_incremental_by_db_version: cr-sqlite extension not found");
+            return;
+        }
+        let a = open_replica();
+        a.execute("INSERT INTO tournaments (id, name) VALUES ('t1', 'First')", [])
+            .unwrap();
+        let first = export_changes_conn(&a, 0).unwrap();
+
+        a.execute("INSERT INTO tournaments (id, name) VALUES ('t2', 'Second')", [])
+            .unwrap();
+        let second = export_changes_conn(&a, first.max_db_version).unwrap();
+
+        // Only the rows written after the first export should come back.
+        assert!(second
+            .changes
+            .iter()
+            .all(|c| c.db_version > first.max_db_version));
+        finalize(&a).unwrap();
+    }
+}
@@ -0,0 +1,164 @@
+//! Typed `Tournament` model and CRUD commands.
+//!
+//! Replaces the name-only read path with full rows the UI can edit. Rows
+//! map one-to-one onto the `tournaments` table; writes use positional
+//! parameters and the create path mints and returns the new row's UUID so
+//! the frontend can immediately reference the inserted tournament.
+
+use rusqlite::Row;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::DbPool;
+
+/// A tournament row, mirroring every column of the `tournaments` table.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Tournament {
+    pub id: String,
+    pub name: String,
+    pub region: Option<String>,
+    pub tier: Option<String>,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    pub status: Option<String>,
+    pub created_at: String,
+}
+
+/// Editable fields supplied by the frontend when creating or updating.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TournamentInput {
+    pub name: String,
+    pub region: Option<String>,
+    pub tier: Option<String>,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    pub status: Option<String>,
+}
+
+/// Reject names that are empty or only whitespace.
+///
+/// The schema's `NOT NULL` is happy with `""`, so validate here to keep
+/// blank-named tournaments out of the UI.
+fn validate_name(name: &str) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("tournament name must not be empty".to_string());
+    }
+    Ok(())
+}
+
+/// Map a result row onto a [`Tournament`].
+fn map_row(row: &Row<'_>) -> rusqlite::Result<Tournament> {
+    Ok(Tournament {
+        id: row.get("id")?,
+        name: row.get("name")?,
+        region: row.get("region")?,
+        tier: row.get("tier")?,
+        start_date: row.get("start_date")?,
+        end_date: row.get("end_date")?,
+        status: row.get("status")?,
+        created_at: row.get("created_at")?,
+    })
+}
+
+/// Return every tournament, newest first.
+#[tauri::command]
+pub fn list_tournaments(state: tauri::State<'_, DbPool>) -> Result<Vec<Tournament>, String> {
+    let conn = state.get().map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, name, region, tier, start_date, end_date, status, created_at
+             FROM tournaments ORDER BY created_at DESC, rowid DESC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt.query_map([], map_row).map_err(|e| e.to_string())?;
+
+    let mut tournaments = Vec::new();
+    for row in rows {
+        tournaments.push(row.map_err(|e| e.to_string())?);
+    }
+
+    Ok(tournaments)
+}
+
+/// Insert a new tournament and return its freshly minted UUID.
+///
+/// The id is generated client-side so that concurrent inserts on different
+/// installs never collide once the rows are merged through cr-sqlite.
+#[tauri::command]
+pub fn create_tournament(
+    state: tauri::State<'_, DbPool>,
+    input: TournamentInput,
+) -> Result<String, String> {
+    validate_name(&input.name)?;
+    let conn = state.get().map_err(|e| e.to_string())?;
+
+    let id = Uuid::new_v4().to_string();
+    conn.execute(
+        "INSERT INTO tournaments (id, name, region, tier, start_date, end_date, status)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        rusqlite::params![
+            id,
+            input.name,
+            input.region,
+            input.tier,
+            input.start_date,
+            input.end_date,
+            input.status,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(id)
+}
+
+/// Overwrite the editable fields of the tournament with the given `id`.
+#[tauri::command]
+pub fn update_tournament(
+    state: tauri::State<'_, DbPool>,
+    id: String,
+    input: TournamentInput,
+) -> Result<(), String> {
+    validate_name(&input.name)?;
+    let conn = state.get().map_err(|e| e.to_string())?;
+
+    let affected = conn
+        .execute(
+            "UPDATE tournaments
+             SET name = ?1, region = ?2, tier = ?3, start_date = ?4, end_date = ?5, status = ?6
+             WHERE id = ?7",
+            rusqlite::params![
+                input.name,
+                input.region,
+                input.tier,
+                input.start_date,
+                input.end_date,
+                input.status,
+                id,
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+
+    if affected == 0 {
+        return Err(format!("no tournament with id {id}"));
+    }
+
+    Ok(())
+}
+
+/// Delete the tournament with the given `id`.
+#[tauri::command]
+pub fn delete_tournament(state: tauri::State<'_, DbPool>, id: String) -> Result<(), String> {
+    let conn = state.get().map_err(|e| e.to_string())?;
+
+    let affected = conn
+        .execute("DELETE FROM tournaments WHERE id = ?1", rusqlite::params![id])
+        .map_err(|e| e.to_string())?;
+
+    if affected == 0 {
+        return Err(format!("no tournament with id {id}"));
+    }
+
+    Ok(())
+}